@@ -0,0 +1,98 @@
+use anyhow::{Result, anyhow, bail};
+
+use mctc_canvas_base::Placement;
+
+/// Bounds-checked big-endian reads over a flat byte buffer, so a truncated
+/// or malformed board snapshot fails with an `anyhow` error at the offending
+/// offset instead of panicking.
+pub trait ReadBytesBE {
+    fn c_u8(&self, i: usize) -> Result<u8>;
+    fn c_u16b(&self, i: usize) -> Result<u16>;
+    fn c_u32b(&self, i: usize) -> Result<u32>;
+}
+
+impl ReadBytesBE for [u8] {
+    fn c_u8(&self, i: usize) -> Result<u8> {
+        self.get(i)
+            .copied()
+            .ok_or_else(|| anyhow!("index {i} out of bounds (len {})", self.len()))
+    }
+
+    fn c_u16b(&self, i: usize) -> Result<u16> {
+        let b = self
+            .get(i..i + 2)
+            .ok_or_else(|| anyhow!("index {i}..{} out of bounds (len {})", i + 2, self.len()))?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn c_u32b(&self, i: usize) -> Result<u32> {
+        let b = self
+            .get(i..i + 4)
+            .ok_or_else(|| anyhow!("index {i}..{} out of bounds (len {})", i + 4, self.len()))?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+/// Decode a raw pxls board snapshot (one palette index per pixel, row-major)
+/// into the initial `Placement`s the canvas started from, skipping index 0
+/// (transparent). Every record is stamped with `time_start` so it sorts
+/// before the log's incremental placements once written.
+pub fn decode_snapshot(
+    data: &[u8],
+    (width, height): (u32, u32),
+    time_start: i64,
+) -> Result<Vec<Placement>> {
+    let expected = width as usize * height as usize;
+    if data.len() != expected {
+        bail!(
+            "snapshot length {} does not match {}x{} board ({} expected)",
+            data.len(),
+            width,
+            height,
+            expected
+        );
+    }
+
+    let mut placements = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let index = data.c_u8((y * width + x) as usize)?;
+            if index == 0 {
+                continue;
+            }
+            placements.push(Placement {
+                pos: (x, y),
+                time: time_start,
+                color_index: index as u16,
+            });
+        }
+    }
+    Ok(placements)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode_snapshot_skips_transparent() {
+        let data = [0, 1, 0, 2];
+        let placements = decode_snapshot(&data, (2, 2), 1_000).unwrap();
+        assert_eq!(placements, vec![
+            Placement { pos: (1, 0), time: 1_000, color_index: 1 },
+            Placement { pos: (1, 1), time: 1_000, color_index: 2 },
+        ]);
+    }
+
+    #[test]
+    fn test_decode_snapshot_size_mismatch() {
+        let data = [0, 1, 2];
+        assert!(decode_snapshot(&data, (2, 2), 1_000).is_err());
+    }
+
+    #[test]
+    fn test_c_u16b_out_of_bounds() {
+        let data = [0u8; 1];
+        assert!(data.c_u16b(0).is_err());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod board;
+pub mod palette;
+pub mod pxls;
+pub mod tail;
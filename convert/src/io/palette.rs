@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+
+use mctc_canvas_base::PaletteChunk;
+
+/// One palette entry: a display name and its RGBA color.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaletteEntry {
+    pub name: String,
+    pub rgba: [u8; 4],
+}
+
+/// Load a palette by name or path, returning entries indexed in palette order
+/// (index 0 is always the first entry read).
+///
+/// `source` is either the name of a built-in palette (currently just
+/// `"c86"`) or a path to a palette file: a JSON list of `{name, rgba}`, or a
+/// plain text table of `#RRGGBBAA name` lines.
+pub fn load(source: &str) -> Result<Vec<PaletteEntry>> {
+    if source == "c86" {
+        return Ok(builtin_c86());
+    }
+
+    let path = Path::new(source);
+    let data =
+        std::fs::read_to_string(path).with_context(|| format!("reading palette {source}"))?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        parse_json(&data)
+    } else {
+        parse_text(&data)
+    }
+}
+
+pub fn into_chunk(entries: &[PaletteEntry]) -> PaletteChunk {
+    PaletteChunk {
+        offset: 0,
+        colors: entries.iter().map(|e| e.rgba).collect(),
+    }
+}
+
+/// Check that `index` has a matching palette entry, erroring with the
+/// offending index otherwise.
+pub fn validate_index(entries: &[PaletteEntry], index: u16) -> Result<()> {
+    if (index as usize) >= entries.len() {
+        bail!("color index {index} has no matching palette entry (palette has {} colors)", entries.len());
+    }
+    Ok(())
+}
+
+fn parse_json(data: &str) -> Result<Vec<PaletteEntry>> {
+    Ok(serde_json::from_str(data)?)
+}
+
+fn parse_text(data: &str) -> Result<Vec<PaletteEntry>> {
+    let mut entries = Vec::new();
+    for line in data.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (hex, name) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| anyhow!("malformed palette line: {line:?}"))?;
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if hex.len() != 8 {
+            bail!("expected #RRGGBBAA, found {hex:?}");
+        }
+
+        let mut rgba = [0u8; 4];
+        for (i, byte) in rgba.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)?;
+        }
+        entries.push(PaletteEntry {
+            name: name.trim().to_string(),
+            rgba,
+        });
+    }
+    Ok(entries)
+}
+
+/// The 33-color palette pxls canvas 86 shipped with, kept as the default so
+/// existing c86 conversions don't need a `--palette` file.
+fn builtin_c86() -> Vec<PaletteEntry> {
+    [
+        ("Transparent", [0x00, 0x00, 0x00, 0x00]),
+        ("Light Grey", [0xFF, 0xFF, 0xFF, 0xFF]),
+        ("Medium Grey", [0xb9, 0xb3, 0xcf, 0xFF]),
+        ("Dark Grey", [0x77, 0x7f, 0x8c, 0xFF]),
+        ("Black", [0x00, 0x00, 0x00, 0xFF]),
+        ("Dark Chocolate", [0x38, 0x22, 0x15, 0xFF]),
+        ("Chocolate", [0x7c, 0x3f, 0x20, 0xff]),
+        ("Brown", [0xc0, 0x6f, 0x37, 0xff]),
+        ("Peach", [0xfe, 0xad, 0x6c, 0xff]),
+        ("Beige", [0xff, 0xd2, 0xb1, 0xff]),
+        ("Pink", [0xff, 0xa4, 0xd0, 0xff]),
+        ("Magenta", [0xf1, 0x4f, 0xb4, 0xff]),
+        ("Mauve", [0xe9, 0x73, 0xff, 0xff]),
+        ("Purple", [0xa6, 0x30, 0xd2, 0xff]),
+        ("Dark Purple", [0x53, 0x1d, 0x8c, 0xff]),
+        ("Navy", [0x24, 0x23, 0x67, 0xff]),
+        ("Blue", [0x03, 0x34, 0xbf, 0xff]),
+        ("Azure", [0x14, 0x9c, 0xff, 0xff]),
+        ("Aqua", [0x8d, 0xf5, 0xff, 0xff]),
+        ("Light Teal", [0x01, 0xbf, 0xa5, 0xff]),
+        ("Dark Teal", [0x16, 0x77, 0x7e, 0xff]),
+        ("Forest", [0x05, 0x45, 0x23, 0xff]),
+        ("Dark Green", [0x18, 0x86, 0x2f, 0xff]),
+        ("Green", [0x61, 0xe0, 0x21, 0xff]),
+        ("Lime", [0xb1, 0xff, 0x37, 0xff]),
+        ("Pastel Yellow", [0xff, 0xff, 0xa5, 0xff]),
+        ("Yellow", [0xfd, 0xe1, 0x11, 0xff]),
+        ("Orange", [0xff, 0x9f, 0x17, 0xff]),
+        ("Rust", [0xf6, 0x6e, 0x08, 0xff]),
+        ("Maroon", [0x55, 0x00, 0x22, 0xff]),
+        ("Rose", [0x99, 0x01, 0x1a, 0xff]),
+        ("Red", [0xf3, 0x0f, 0x0c, 0xff]),
+        ("Watermelon", [0xff, 0x78, 0x72, 0xff]),
+    ]
+    .into_iter()
+    .map(|(name, rgba)| PaletteEntry {
+        name: name.to_string(),
+        rgba,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_text() {
+        let data = "#000000FF Black\n#FFFFFFff White\n";
+        let entries = parse_text(data).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "Black");
+        assert_eq!(entries[0].rgba, [0x00, 0x00, 0x00, 0xFF]);
+        assert_eq!(entries[1].name, "White");
+        assert_eq!(entries[1].rgba, [0xFF, 0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_parse_text_malformed() {
+        assert!(parse_text("not a palette line").is_err());
+    }
+
+    #[test]
+    fn test_parse_text_bad_hex_length() {
+        assert!(parse_text("#FFF White").is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let data = r#"[{"name": "Black", "rgba": [0, 0, 0, 255]}]"#;
+        let entries = parse_json(data).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "Black");
+        assert_eq!(entries[0].rgba, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_validate_index() {
+        let entries = vec![PaletteEntry { name: "Black".to_string(), rgba: [0, 0, 0, 255] }];
+        assert!(validate_index(&entries, 0).is_ok());
+        assert!(validate_index(&entries, 1).is_err());
+    }
+
+    #[test]
+    fn test_load_builtin_c86() {
+        let entries = load("c86").unwrap();
+        assert_eq!(entries.len(), 33);
+        assert_eq!(entries[0].name, "Transparent");
+    }
+}
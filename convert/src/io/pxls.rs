@@ -72,9 +72,9 @@ impl Display for PxlsLine {
 }
 
 impl PxlsLine {
-    fn parse_bytes(data: &[u8]) -> Result<Self> {
+    pub(crate) fn parse_bytes(data: &[u8]) -> Result<Self> {
         let mut bytes = data.split(|&b| b == b'\n' || b == b'\t');
-        let time = read_time(bytes.next().ok_or_else(|| anyhow!("unexpected eof"))?)?;
+        let time = parse_time(bytes.next().ok_or_else(|| anyhow!("unexpected eof"))?)?;
         let id = read_userid(bytes.next().ok_or_else(|| anyhow!("unexpected eof"))?)?;
         let x = read_int(bytes.next().ok_or_else(|| anyhow!("unexpected eof"))?)?;
         let y = read_int(bytes.next().ok_or_else(|| anyhow!("unexpected eof"))?)?;
@@ -104,18 +104,25 @@ pub struct PxlsFile {
 }
 
 impl PxlsFile {
-    pub fn read_from(mut rdr: impl BufRead) -> Result<Self> {
-        let mut lines = Vec::new();
-        loop {
+    pub fn read_from(rdr: impl BufRead) -> Result<Self> {
+        let lines = Self::stream(rdr).collect::<Result<Vec<_>>>()?;
+        Ok(PxlsFile { lines })
+    }
+
+    /// Parse `rdr` one line at a time without buffering the whole log in memory.
+    ///
+    /// Unlike [`PxlsFile::read_from`], this never materializes a `Vec<PxlsLine>`,
+    /// so it's the entry point to use against multi-gigabyte pxls.space exports.
+    pub fn stream(mut rdr: impl BufRead) -> impl Iterator<Item = Result<PxlsLine>> {
+        std::iter::from_fn(move || {
             // TODO: Vec::with_capacity (determine maximum *reasonable* line length)
             let mut dyn_buf = Vec::new();
-            if rdr.read_until(b'\n', &mut dyn_buf)? == 0 {
-                break;
+            match rdr.read_until(b'\n', &mut dyn_buf) {
+                Ok(0) => None,
+                Ok(_) => Some(PxlsLine::parse_bytes(&dyn_buf)),
+                Err(e) => Some(Err(e.into())),
             }
-            lines.push(PxlsLine::parse_bytes(&dyn_buf)?);
-        }
-
-        Ok(PxlsFile { lines })
+        })
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &PxlsLine> {
@@ -127,11 +134,30 @@ impl PxlsFile {
     }
 }
 
-fn read_time(data: &[u8]) -> Result<i64> {
-    Ok(
-        NaiveDateTime::parse_from_str(std::str::from_utf8(data)?, DATE_FMT)
-            .map(|t| t.and_utc().timestamp_millis())?,
-    )
+/// Parse a log time field, auto-detecting its encoding.
+///
+/// Tries, in order: the pxls export format (`DATE_FMT`), ISO-8601/RFC-3339,
+/// and a bare integer epoch (milliseconds or seconds, disambiguated by
+/// magnitude), normalizing the result to epoch milliseconds. pxls exports and
+/// third-party re-exports don't agree on a single time column format, so
+/// callers shouldn't have to know which one they were handed.
+pub fn parse_time(data: &[u8]) -> Result<i64> {
+    let text = std::str::from_utf8(data)?.trim();
+
+    if let Ok(t) = NaiveDateTime::parse_from_str(text, DATE_FMT) {
+        return Ok(t.and_utc().timestamp_millis());
+    }
+    if let Ok(t) = DateTime::parse_from_rfc3339(text) {
+        return Ok(t.timestamp_millis());
+    }
+    if let Ok(n) = text.parse::<i64>() {
+        // Epoch seconds and epoch millis differ by three orders of
+        // magnitude for any realistic pxls timestamp, so the split point
+        // below (~ the year 2001 in millis) cleanly tells them apart.
+        return Ok(if n.abs() >= 1_000_000_000_000 { n } else { n * 1000 });
+    }
+
+    bail!("unrecognized time format ({text:?})")
 }
 
 fn read_userid(data: &[u8]) -> Result<PxlsUserId> {
@@ -232,4 +258,29 @@ mod test {
         let output = PxlsLine::parse_bytes(data).unwrap();
         assert_eq!(&output.to_string(), std::str::from_utf8(data).unwrap())
     }
+
+    #[test]
+    fn test_parse_time_textual() {
+        assert_eq!(parse_time(b"2021-03-19 08:03:47,016").unwrap(), 1616141027016);
+    }
+
+    #[test]
+    fn test_parse_time_rfc3339() {
+        assert_eq!(parse_time(b"2021-03-19T08:03:47.016Z").unwrap(), 1616141027016);
+    }
+
+    #[test]
+    fn test_parse_time_epoch_millis() {
+        assert_eq!(parse_time(b"1616141027016").unwrap(), 1616141027016);
+    }
+
+    #[test]
+    fn test_parse_time_epoch_seconds() {
+        assert_eq!(parse_time(b"1616141027").unwrap(), 1616141027000);
+    }
+
+    #[test]
+    fn test_parse_time_invalid() {
+        assert!(parse_time(b"not a time").is_err());
+    }
 }
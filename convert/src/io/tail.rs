@@ -0,0 +1,62 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+use anyhow::Result;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::pxls::PxlsLine;
+
+/// Follows a pxls log file the way `tail -f` would, yielding newly written
+/// [`PxlsLine`]s as they're appended to the file on disk.
+pub struct Tail {
+    reader: BufReader<File>,
+    events: Receiver<notify::Result<Event>>,
+    // Kept alive only to keep the underlying OS watch registered.
+    _watcher: RecommendedWatcher,
+    carry: Vec<u8>,
+}
+
+impl Tail {
+    /// Open `path` for following, starting from its current end-of-file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut reader = BufReader::new(File::open(path)?);
+        reader.seek(SeekFrom::End(0))?;
+
+        let (tx, events) = channel();
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        })?;
+        watcher.watch(path, RecursiveMode::NonRecursive)?;
+
+        Ok(Tail {
+            reader,
+            events,
+            _watcher: watcher,
+            carry: Vec::new(),
+        })
+    }
+
+    /// Wait up to `timeout` for the file to grow, returning the complete
+    /// lines appended since the last call. Returns an empty `Vec` on
+    /// timeout so the caller can poll for shutdown in between.
+    pub fn poll(&mut self, timeout: Duration) -> Result<Vec<PxlsLine>> {
+        match self.events.recv_timeout(timeout) {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => return Err(e.into()),
+            Err(_) => return Ok(Vec::new()),
+        }
+
+        self.reader.read_to_end(&mut self.carry)?;
+
+        let mut lines = Vec::new();
+        while let Some(pos) = self.carry.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.carry.drain(..=pos).collect();
+            lines.push(PxlsLine::parse_bytes(&line)?);
+        }
+        Ok(lines)
+    }
+}
@@ -1,54 +1,160 @@
-use std::fs::OpenOptions;
-use std::io::{stdin, BufReader, BufWriter};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Result, anyhow, bail};
-use io::pxls::PxlsFile;
-use mctc_canvas_base::{CanvasBaseCodec, CanvasEvent, CanvasMeta, MetaId, PaletteChunk, Placement};
+use clap::Parser;
+use io::board::decode_snapshot;
+use io::palette::PaletteEntry;
+use io::pxls::{PxlsAction, PxlsFile, PxlsLine, PxlsUserId};
+use io::tail::Tail;
+use mctc_canvas_base::{
+    CanvasBaseCodec, CanvasEvent, CanvasMeta, MetaId, Overwrite, Placement, RegionClear, Revert,
+};
 use mctc_parser::Codec;
 use mctc_parser::data::{Header, Record};
+use mctc_parser::reader::{read_header, read_record};
 use mctc_parser::writer::{write_header, write_record};
 
 pub mod io;
 
+/// User id recorded against board-snapshot placements, which have no real
+/// pxls.space user behind them but still need a `MetaId` to pair with, like
+/// every other placement/action event this tool writes.
+const BOARD_SEED_ID: &[u8] = b"board-seed";
+
+/// Convert a pxls.space log export into an MCTC canvas history.
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Cli {
+    /// Path to write the resulting `.mctc` file to.
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Canvas name recorded in `CanvasMeta` (e.g. "c86"). Required unless
+    /// `--reverse`, which has no `CanvasMeta` to write.
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Platform the canvas was hosted on (e.g. "pxls.space").
+    #[arg(short, long, default_value = "pxls.space")]
+    platform: String,
+
+    /// pxls log file to read. Required in forward mode: the size pre-scan
+    /// reads the log twice, which a stdin pipe can't support without
+    /// buffering the whole thing in memory first.
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// Keep running and record new lines as they're appended to `--input`,
+    /// writing the EOS record only on Ctrl-C. Requires `--input`.
+    #[arg(long)]
+    follow: bool,
+
+    /// Raw board snapshot (one palette index per pixel, row-major) to seed
+    /// the canvas's initial state from, before the log's own placements.
+    /// Requires `--board-width`/`--board-height`.
+    #[arg(long)]
+    board: Option<PathBuf>,
+
+    /// Width of the `--board` snapshot, in pixels.
+    #[arg(long)]
+    board_width: Option<u32>,
+
+    /// Height of the `--board` snapshot, in pixels.
+    #[arg(long)]
+    board_height: Option<u32>,
+
+    /// Palette to use: the built-in "c86" palette, or a path to a JSON or
+    /// `#RRGGBBAA name` text palette file.
+    #[arg(long, default_value = "c86")]
+    palette: String,
+
+    /// Run the inverse conversion: read an `.mctc` file from `--input` and
+    /// write it back out as a pxls.space log to `--output`. `rollback`,
+    /// `rollback undo` and `mod overwrite` lines all collapse onto
+    /// `CanvasEvent::Overwrite` on the way in, so a round trip writes all
+    /// three back as `mod overwrite`.
+    #[arg(long)]
+    reverse: bool,
+}
+
 fn main() -> Result<()> {
+    let cli = Cli::parse();
+    if cli.follow && cli.input.is_none() {
+        bail!("--follow requires --input (there's nothing to tail on stdin)");
+    }
+    if !cli.reverse && cli.name.is_none() {
+        bail!("--name is required unless --reverse");
+    }
+    if !cli.reverse && cli.input.is_none() {
+        bail!("--input is required (reads from stdin can't be rewound for the bounds pre-scan)");
+    }
+
+    if cli.reverse {
+        let input = cli
+            .input
+            .as_ref()
+            .ok_or_else(|| anyhow!("--reverse requires --input (the .mctc file to read)"))?;
+        let mut wtr = BufWriter::new(
+            OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&cli.output)?,
+        );
+        return export_pxls(input, &mut wtr);
+    }
+
     let mut header = Header::default();
     let id = header
         .register_codec::<CanvasBaseCodec>()
         .ok_or(anyhow!("failed to register codec"))?;
     let mut codec = CanvasBaseCodec::new(id);
 
-    let destination = std::env::var("HOME").unwrap().to_string() + "/pxls/out/c86.mctc";
-    eprintln!("Opening file... {}", destination);
+    eprintln!("Opening file... {}", cli.output.display());
     let mut wtr = BufWriter::new(OpenOptions::new()
         .create(true)
         .write(true)
         .truncate(true)
-        .open(destination)?);
+        .open(&cli.output)?);
 
-    eprintln!("Reading stdin...");
-    let input = stdin();
-    let file = PxlsFile::read_from(BufReader::new(input))?;
-    if file.lines().is_empty() {
-        bail!("file is empty");
-    }
-    eprintln!("Read {} lines!", { file.lines().len() });
+    // Opened twice - once for the bounds pre-scan, once for the write pass -
+    // so neither pass ever holds the full log in RAM.
+    let input = cli.input.as_ref().expect("validated above: --input is required");
+    let open_input = || -> Result<BufReader<File>> { Ok(BufReader::new(File::open(input)?)) };
 
-    let time_start = file.lines().first().unwrap().time;
-    let time_end = file.lines().last().unwrap().time;
-    let mut size = (0, 0);
-    for line in file.iter() {
-        size.0 = size.0.max(line.pos.0 + 1);
-        size.1 = size.1.max(line.pos.1 + 1);
+    let board_size = match (cli.board.is_some(), cli.board_width, cli.board_height) {
+        (true, Some(w), Some(h)) => Some((w, h)),
+        (true, _, _) => bail!("--board requires --board-width and --board-height"),
+        (false, _, _) => None,
+    };
+
+    eprintln!("Scanning for canvas bounds...");
+    let (mut size, time_start, time_end) = scan_bounds(PxlsFile::stream(open_input()?))?;
+    // The log only bounds the pixels it touches; a board snapshot covers the
+    // whole canvas, so it can only grow the recorded size, never shrink it.
+    if let Some((board_width, board_height)) = board_size {
+        size.0 = size.0.max(board_width);
+        size.1 = size.1.max(board_height);
     }
 
     eprintln!("Size {:?}", size);
-    eprintln!("Duration ({} ms)", time_end - time_start);
+    // In follow mode the log keeps growing after this point, so there's no
+    // real end time yet; it's left unset rather than pinned to the scan.
+    let time_end = if cli.follow { None } else { Some(time_end) };
+    if let Some(time_end) = time_end {
+        eprintln!("Duration ({} ms)", time_end - time_start);
+    }
     let meta = CanvasMeta {
         size,
         time_start,
-        time_end: Some(time_end),
-        name: "c86".to_string(),
-        platform: "pxls.space".to_string(),
+        time_end,
+        name: cli.name.unwrap(),
+        platform: cli.platform,
     };
 
     // TODO: Better write api
@@ -56,59 +162,48 @@ fn main() -> Result<()> {
     let now = std::time::SystemTime::now();
     write_header(&mut wtr, &header)?;
 
+    let palette = io::palette::load(&cli.palette)?;
     codec.write_record(&mut wtr, &CanvasEvent::CanvasMeta(meta))?;
     codec.write_record(
         &mut wtr,
-        &CanvasEvent::PaletteChunk(PaletteChunk {
-            offset: 0,
-            colors: vec![
-                [0x00, 0x00, 0x00, 0x00], // Transparent
-                [0xFF, 0xFF, 0xFF, 0xFF], // Light Grey
-                [0xb9, 0xb3, 0xcf, 0xFF], // Medium Grey
-                [0x77, 0x7f, 0x8c, 0xFF], // Dark Grey
-                [0x00, 0x00, 0x00, 0xFF], // Black
-                [0x38, 0x22, 0x15, 0xFF], // Dark Chocolate
-                [0x7c, 0x3f, 0x20, 0xff], // Chocolate
-                [0xc0, 0x6f, 0x37, 0xff], // Brown
-                [0xfe, 0xad, 0x6c, 0xff], // Peach
-                [0xff, 0xd2, 0xb1, 0xff], // Beige
-                [0xff, 0xa4, 0xd0, 0xff], // Pink
-                [0xf1, 0x4f, 0xb4, 0xff], // Magenta
-                [0xe9, 0x73, 0xff, 0xff], // Mauve
-                [0xa6, 0x30, 0xd2, 0xff], // Purple
-                [0x53, 0x1d, 0x8c, 0xff], // Dark Purple
-                [0x24, 0x23, 0x67, 0xff], // Navy
-                [0x03, 0x34, 0xbf, 0xff], // Blue
-                [0x14, 0x9c, 0xff, 0xff], // Azure
-                [0x8d, 0xf5, 0xff, 0xff], // Aqua
-                [0x01, 0xbf, 0xa5, 0xff], // Light Teal
-                [0x16, 0x77, 0x7e, 0xff], // Dark Teal
-                [0x05, 0x45, 0x23, 0xff], // Forest
-                [0x18, 0x86, 0x2f, 0xff], // Dark Green
-                [0x61, 0xe0, 0x21, 0xff], // Green
-                [0xb1, 0xff, 0x37, 0xff], // Lime
-                [0xff, 0xff, 0xa5, 0xff], // Pastel Yellow
-                [0xfd, 0xe1, 0x11, 0xff], // Yellow
-                [0xff, 0x9f, 0x17, 0xff], // Orange
-                [0xf6, 0x6e, 0x08, 0xff], // Rust
-                [0x55, 0x00, 0x22, 0xff], // Maroon
-                [0x99, 0x01, 0x1a, 0xff], // Rose
-                [0xf3, 0x0f, 0x0c, 0xff], // Red
-                [0xff, 0x78, 0x72, 0xff], // Watermelon
-            ],
-        }),
+        &CanvasEvent::PaletteChunk(io::palette::into_chunk(&palette)),
     )?;
 
-    for line in file.iter() {
-        let color_index = if line.index == 255 { 0 } else { line.index + 1 };
-        let place = Placement {
-            pos: line.pos,
-            time: line.time,
-            color_index,
-        };
-        let id = MetaId::Numerical(line.id.as_str().as_bytes().to_vec());
-        codec.write_record(&mut wtr, &CanvasEvent::Placement(place))?;
-        codec.write_record(&mut wtr, &CanvasEvent::MetaId(id))?;
+    if let Some(board) = &cli.board {
+        let board_dims = board_size.expect("validated above: --board implies board_size");
+        eprintln!("Seeding board from {}...", board.display());
+        let data = std::fs::read(board)?;
+        for place in decode_snapshot(&data, board_dims, time_start)? {
+            io::palette::validate_index(&palette, place.color_index)?;
+            codec.write_record(&mut wtr, &CanvasEvent::Placement(place))?;
+            codec.write_record(
+                &mut wtr,
+                &CanvasEvent::MetaId(MetaId::Numerical(BOARD_SEED_ID.to_vec())),
+            )?;
+        }
+    }
+
+    for line in PxlsFile::stream(open_input()?) {
+        write_line(&mut wtr, &mut codec, line?, &palette)?;
+    }
+
+    if cli.follow {
+        wtr.flush()?;
+        eprintln!("Following {}... (Ctrl-C to stop)", cli.input.as_ref().unwrap().display());
+
+        let running = Arc::new(AtomicBool::new(true));
+        {
+            let running = running.clone();
+            ctrlc::set_handler(move || running.store(false, Ordering::SeqCst))?;
+        }
+
+        let mut tail = Tail::open(cli.input.as_ref().unwrap())?;
+        while running.load(Ordering::SeqCst) {
+            for line in tail.poll(Duration::from_millis(500))? {
+                write_line(&mut wtr, &mut codec, line, &palette)?;
+            }
+            wtr.flush()?;
+        }
     }
 
     // eos
@@ -117,3 +212,227 @@ fn main() -> Result<()> {
     eprintln!("Write took {} ms", now.elapsed()?.as_millis());
     Ok(())
 }
+
+/// Map one `PxlsLine` onto the `CanvasEvent` variant matching its action,
+/// rather than collapsing every mutation kind into a `Placement`.
+fn write_line(
+    wtr: &mut impl Write,
+    codec: &mut CanvasBaseCodec,
+    line: PxlsLine,
+    palette: &[PaletteEntry],
+) -> Result<()> {
+    let color_index = if line.index == 255 { 0 } else { line.index + 1 };
+    io::palette::validate_index(palette, color_index)?;
+    let event = match line.action {
+        PxlsAction::Place => CanvasEvent::Placement(Placement {
+            pos: line.pos,
+            time: line.time,
+            color_index,
+        }),
+        PxlsAction::Undo => CanvasEvent::Revert(Revert {
+            pos: line.pos,
+            time: line.time,
+        }),
+        PxlsAction::Rollback | PxlsAction::RollbackUndo | PxlsAction::Overwrite => {
+            CanvasEvent::Overwrite(Overwrite {
+                pos: line.pos,
+                time: line.time,
+                color_index,
+            })
+        }
+        PxlsAction::Nuke => CanvasEvent::RegionClear(RegionClear {
+            pos: line.pos,
+            time: line.time,
+        }),
+    };
+
+    let id = MetaId::Numerical(line.id.as_str().as_bytes().to_vec());
+    codec.write_record(wtr, &event)?;
+    codec.write_record(wtr, &CanvasEvent::MetaId(id))?;
+    Ok(())
+}
+
+/// Read an `.mctc` canvas history and write it back out as a pxls.space log,
+/// reversing the `Placement`/action-event + `MetaId` pairing used on the way in.
+fn export_pxls(input: &PathBuf, wtr: &mut impl Write) -> Result<()> {
+    let mut rdr = BufReader::new(File::open(input)?);
+    let mut header = Header::default();
+    let id = header
+        .register_codec::<CanvasBaseCodec>()
+        .ok_or_else(|| anyhow!("failed to register codec"))?;
+    let mut codec = CanvasBaseCodec::new(id);
+    read_header(&mut rdr, &mut header)?;
+
+    let mut pending: Option<CanvasEvent> = None;
+    loop {
+        let record = read_record(&mut rdr)?;
+        if record.is_eos() {
+            break;
+        }
+
+        match (pending.take(), codec.read_record(&record)?) {
+            // CanvasMeta/PaletteChunk are written once, up front, with no
+            // MetaId following them - they carry no pxls.space log line and
+            // are consumed here rather than fed into the placement pairing.
+            (None, CanvasEvent::CanvasMeta(_) | CanvasEvent::PaletteChunk(_)) => {}
+            (None, CanvasEvent::MetaId(_)) => bail!("MetaId record with no preceding placement"),
+            (None, event) => pending = Some(event),
+            (Some(event), CanvasEvent::MetaId(id)) => {
+                writeln!(wtr, "{}", event_to_line(event, id)?)?;
+            }
+            (Some(_), _) => bail!("expected a MetaId record to follow a placement"),
+        }
+    }
+    Ok(())
+}
+
+/// Invert one `CanvasEvent` + its `MetaId` back into the `PxlsLine` it came
+/// from, undoing the `color_index` remap (index 0 -> 255) and picking the
+/// `PxlsAction` the event variant was mapped from.
+///
+/// The moderation actions (`Rollback`/`RollbackUndo`/`Overwrite`) all collapse
+/// onto `CanvasEvent::Overwrite`, so that distinction can't be recovered here
+/// — it's reported back as a plain `Rollback`. `Revert` and `RegionClear`
+/// carry no color, so their lines come back with index 0 rather than the
+/// original one.
+fn event_to_line(event: CanvasEvent, id: MetaId) -> Result<PxlsLine> {
+    let (pos, time, index, action) = match event {
+        CanvasEvent::Placement(p) => (p.pos, p.time, reverse_color_index(p.color_index), PxlsAction::Place),
+        CanvasEvent::Revert(r) => (r.pos, r.time, 0, PxlsAction::Undo),
+        CanvasEvent::Overwrite(o) => (o.pos, o.time, reverse_color_index(o.color_index), PxlsAction::Rollback),
+        CanvasEvent::RegionClear(c) => (c.pos, c.time, 0, PxlsAction::Nuke),
+        other => bail!("{other:?} has no pxls.space log equivalent"),
+    };
+
+    let id = match id {
+        MetaId::Numerical(bytes) => String::from_utf8(bytes)?,
+        other => bail!("{other:?} has no pxls.space user-id equivalent"),
+    };
+    let id = if id.len() == 64 {
+        PxlsUserId::Sha256(id)
+    } else {
+        PxlsUserId::Username(id)
+    };
+
+    Ok(PxlsLine { time, pos, index, action, id })
+}
+
+fn reverse_color_index(index: u16) -> u16 {
+    if index == 0 { 255 } else { index - 1 }
+}
+
+/// Compute the canvas size and time range from a line stream without
+/// collecting it into a `Vec` first.
+fn scan_bounds(lines: impl Iterator<Item = Result<PxlsLine>>) -> Result<((u32, u32), i64, i64)> {
+    let mut size = (0u32, 0u32);
+    let mut time_start = None;
+    let mut time_end = i64::MIN;
+    for line in lines {
+        let line = line?;
+        size.0 = size.0.max(line.pos.0 + 1);
+        size.1 = size.1.max(line.pos.1 + 1);
+        time_start.get_or_insert(line.time);
+        time_end = line.time;
+    }
+    let time_start = time_start.ok_or_else(|| anyhow!("file is empty"))?;
+    Ok((size, time_start, time_end))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reverse_color_index() {
+        assert_eq!(reverse_color_index(0), 255);
+        assert_eq!(reverse_color_index(5), 4);
+    }
+
+    #[test]
+    fn test_event_to_line_placement_roundtrips() {
+        let event = CanvasEvent::Placement(Placement { pos: (3, 4), time: 100, color_index: 5 });
+        let id = MetaId::Numerical(b"Etos2".to_vec());
+        let line = event_to_line(event, id).unwrap();
+        assert_eq!(line.pos, (3, 4));
+        assert_eq!(line.time, 100);
+        assert_eq!(line.index, 4);
+        assert_eq!(line.action, PxlsAction::Place);
+        assert_eq!(line.id, PxlsUserId::Username("Etos2".to_string()));
+    }
+
+    /// Pins the known-lossy leg of the round trip: `RollbackUndo` is merged
+    /// into `CanvasEvent::Overwrite` on the way in (chunk0-4), so it comes
+    /// back out as a plain `Rollback`, not the original action string.
+    #[test]
+    fn test_event_to_line_overwrite_is_reported_as_rollback() {
+        let event = CanvasEvent::Overwrite(Overwrite { pos: (1, 2), time: 50, color_index: 0 });
+        let id = MetaId::Numerical(b"Etos2".to_vec());
+        let line = event_to_line(event, id).unwrap();
+        assert_eq!(line.action, PxlsAction::Rollback);
+        assert_eq!(line.index, 255);
+    }
+
+    #[test]
+    fn test_event_to_line_rejects_non_pxls_event() {
+        let event = CanvasEvent::CanvasMeta(CanvasMeta {
+            size: (1, 1),
+            time_start: 0,
+            time_end: None,
+            name: "c86".to_string(),
+            platform: "pxls.space".to_string(),
+        });
+        let id = MetaId::Numerical(b"Etos2".to_vec());
+        assert!(event_to_line(event, id).is_err());
+    }
+
+    /// A board-seeded `.mctc` file round-tripped through `--reverse`: the
+    /// `CanvasMeta`/`PaletteChunk` pair is skipped, and the board `Placement`
+    /// pairs with its `BOARD_SEED_ID` `MetaId` just like any other event.
+    #[test]
+    fn test_export_pxls_board_seed_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("canvas-explorer-test-{}.mctc", std::process::id()));
+
+        let mut header = Header::default();
+        let id = header.register_codec::<CanvasBaseCodec>().unwrap();
+        let mut codec = CanvasBaseCodec::new(id);
+        let mut wtr = BufWriter::new(File::create(&path).unwrap());
+        write_header(&mut wtr, &header).unwrap();
+        codec
+            .write_record(&mut wtr, &CanvasEvent::CanvasMeta(CanvasMeta {
+                size: (2, 2),
+                time_start: 0,
+                time_end: None,
+                name: "c86".to_string(),
+                platform: "pxls.space".to_string(),
+            }))
+            .unwrap();
+        codec
+            .write_record(&mut wtr, &CanvasEvent::PaletteChunk(io::palette::into_chunk(&[])))
+            .unwrap();
+        codec
+            .write_record(&mut wtr, &CanvasEvent::Placement(Placement {
+                pos: (0, 0),
+                time: 0,
+                color_index: 1,
+            }))
+            .unwrap();
+        codec
+            .write_record(
+                &mut wtr,
+                &CanvasEvent::MetaId(MetaId::Numerical(BOARD_SEED_ID.to_vec())),
+            )
+            .unwrap();
+        write_record(&mut wtr, &Record::new_eos()).unwrap();
+        wtr.flush().unwrap();
+        drop(wtr);
+
+        let mut out = Vec::new();
+        export_pxls(&path, &mut out).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let line = std::str::from_utf8(&out).unwrap().trim();
+        assert!(line.ends_with("user place"), "expected a place line, got {line:?}");
+        assert!(line.contains("board-seed"));
+    }
+}